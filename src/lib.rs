@@ -0,0 +1,459 @@
+use std::{collections::HashSet, fmt::Display};
+
+use psl::{
+    parse_schema_multi,
+    parser_database::walkers::{RefinedRelationWalker, RelationWalker, ScalarFieldWalker, Walker},
+    schema_ast::ast::{CompositeTypeId, EnumId, Field, FieldType, ModelId, WithDocumentation},
+    SourceFile, ValidatedSchema,
+};
+
+/// Which models/enums make it into the diagram, mirroring the `--only`/
+/// `--except` flags of schema-printing tools.
+pub enum Filtering {
+    OnlyTables(Vec<String>),
+    ExceptTables(Vec<String>),
+    None,
+}
+
+impl Filtering {
+    pub fn from_args(only: Vec<String>, except: Vec<String>) -> Self {
+        if !only.is_empty() {
+            Filtering::OnlyTables(only)
+        } else if !except.is_empty() {
+            Filtering::ExceptTables(except)
+        } else {
+            Filtering::None
+        }
+    }
+
+    fn should_ignore(&self, name: &str) -> bool {
+        match self {
+            Filtering::OnlyTables(names) => !names.iter().any(|n| n == name),
+            Filtering::ExceptTables(names) => names.iter().any(|n| n == name),
+            Filtering::None => false,
+        }
+    }
+}
+
+pub struct RenderOptions {
+    pub render_enums: bool,
+    pub render_relations: bool,
+    pub cardinality_labels: bool,
+    pub docs: bool,
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        RenderOptions {
+            render_enums: true,
+            render_relations: true,
+            cardinality_labels: true,
+            docs: true,
+        }
+    }
+}
+
+pub struct D2Diagram {
+    sql_tables: Vec<D2SqlTable>,
+    enums: Vec<D2Enum>,
+    relations: Vec<D2Relation>,
+}
+
+impl D2Diagram {
+    fn new() -> Self {
+        D2Diagram {
+            sql_tables: vec![],
+            enums: vec![],
+            relations: vec![],
+        }
+    }
+}
+
+/// Distinguishes the d2 shapes/labels rendered for the node kinds that
+/// `sql_table` covers: actual tables, read-only views, and (Mongo) composite
+/// types, which have columns but no identity of their own.
+enum D2TableKind {
+    Table,
+    View,
+    Composite,
+}
+
+struct D2SqlTable {
+    name: String,
+    kind: D2TableKind,
+    doc: Option<String>,
+    columns: Vec<D2SqlColumn>,
+}
+
+impl D2SqlTable {
+    fn with_name(name: String) -> Self {
+        D2SqlTable {
+            name,
+            kind: D2TableKind::Table,
+            doc: None,
+            columns: vec![],
+        }
+    }
+}
+
+struct D2Enum {
+    name: String,
+    doc: Option<String>,
+    values: Vec<String>,
+}
+
+struct D2SqlColumn {
+    name: String,
+    datatype: String,
+    doc: Option<String>,
+    constraints: Vec<SqlConstraint>,
+}
+
+impl D2SqlColumn {
+    fn with_name_and_datatype(name: String, datatype: String) -> Self {
+        D2SqlColumn {
+            name,
+            datatype,
+            doc: None,
+            constraints: vec![],
+        }
+    }
+}
+
+enum SqlConstraint {
+    PrimaryKey,
+    ForeignKey,
+    Unique,
+}
+
+struct D2Relation {
+    from: String,
+    to: String,
+    label: Option<String>,
+}
+
+/// Parses `sources` as a (possibly multi-file) Prisma schema and renders it.
+pub fn render_schema(
+    sources: Vec<(String, SourceFile)>,
+    filtering: &Filtering,
+    options: &RenderOptions,
+) -> Result<D2Diagram, String> {
+    let parsed = parse_schema_multi(sources)?;
+    Ok(render(&parsed, filtering, options))
+}
+
+fn render(schema: &ValidatedSchema, filtering: &Filtering, options: &RenderOptions) -> D2Diagram {
+    let mut diagram = D2Diagram::new();
+    let mut excluded_node_names: HashSet<String> = HashSet::new();
+
+    for r#enum in schema.db.walk_enums() {
+        if !options.render_enums || filtering.should_ignore(r#enum.name()) {
+            excluded_node_names.insert(r#enum.name().to_owned());
+            continue;
+        }
+        diagram.enums.push(render_enum(r#enum, options.docs));
+    }
+    for composite_type in schema.db.walk_composite_types() {
+        if filtering.should_ignore(composite_type.name()) {
+            excluded_node_names.insert(composite_type.name().to_owned());
+            continue;
+        }
+        diagram
+            .sql_tables
+            .push(render_composite_type(composite_type, options.docs));
+    }
+
+    let enum_names: HashSet<&str> = diagram.enums.iter().map(|e| e.name.as_str()).collect();
+    let composite_names: HashSet<&str> = diagram
+        .sql_tables
+        .iter()
+        .map(|table| table.name.as_str())
+        .collect();
+
+    for model in schema.db.walk_models() {
+        if filtering.should_ignore(model.name()) {
+            excluded_node_names.insert(model.database_name().to_owned());
+            continue;
+        }
+        let (table, mut relations) = render_model(
+            model,
+            &enum_names,
+            &composite_names,
+            options.docs,
+            options.render_relations,
+        );
+        diagram.sql_tables.push(table);
+        diagram.relations.append(&mut relations);
+    }
+    for view in schema.db.walk_views() {
+        if filtering.should_ignore(view.name()) {
+            excluded_node_names.insert(view.database_name().to_owned());
+            continue;
+        }
+        let (mut table, mut relations) = render_model(
+            view,
+            &enum_names,
+            &composite_names,
+            options.docs,
+            options.render_relations,
+        );
+        table.kind = D2TableKind::View;
+        diagram.sql_tables.push(table);
+        diagram.relations.append(&mut relations);
+    }
+
+    // `walk_relations` already yields one walker per relation (not per relation
+    // field), so the 1:1/1:n/m:n sides are naturally de-duplicated here.
+    if options.render_relations {
+        for relation in schema.db.walk_relations() {
+            render_relation(relation, &mut diagram, options.cardinality_labels);
+        }
+    }
+
+    // A filtered-out model/enum must not leave a dangling edge behind.
+    diagram.relations.retain(|relation| {
+        !excluded_node_names.contains(&relation.from) && !excluded_node_names.contains(&relation.to)
+    });
+
+    diagram
+}
+
+fn render_model(
+    model: Walker<'_, ModelId>,
+    enum_names: &HashSet<&str>,
+    composite_names: &HashSet<&str>,
+    docs: bool,
+    render_relations: bool,
+) -> (D2SqlTable, Vec<D2Relation>) {
+    let table_name = model.database_name().to_owned();
+    let mut table = D2SqlTable::with_name(table_name.clone());
+    if docs {
+        table.doc = model.ast_model().documentation().map(str::to_owned);
+    }
+    let mut relations = vec![];
+    for field in model.fields() {
+        // Relation (object-type) fields are represented by the edges from
+        // `schema.db.walk_relations()` instead; a column here would just
+        // duplicate that edge under a fake "datatype" that is a model name.
+        if field.as_relation_field().is_some() {
+            continue;
+        }
+        let column = render_column(field.name(), field.ast_field(), docs);
+        if render_relations {
+            if let Some(label) = composite_or_enum_label(&column.datatype, enum_names, composite_names) {
+                relations.push(D2Relation {
+                    from: table_name.clone(),
+                    to: column.datatype.clone(),
+                    label: Some(label.to_owned()),
+                });
+            }
+        }
+        table.columns.push(column);
+    }
+    (table, relations)
+}
+
+fn render_composite_type(composite_type: Walker<'_, CompositeTypeId>, docs: bool) -> D2SqlTable {
+    let mut table = D2SqlTable::with_name(composite_type.name().to_owned());
+    table.kind = D2TableKind::Composite;
+    if docs {
+        table.doc = composite_type.ast_composite_type().documentation().map(str::to_owned);
+    }
+    for field in composite_type.fields() {
+        table
+            .columns
+            .push(render_column(field.name(), field.ast_field(), docs));
+    }
+    table
+}
+
+fn render_enum(r#enum: Walker<'_, EnumId>, docs: bool) -> D2Enum {
+    D2Enum {
+        name: r#enum.name().to_owned(),
+        doc: docs
+            .then(|| r#enum.ast_enum().documentation().map(str::to_owned))
+            .flatten(),
+        values: r#enum.values().map(|value| value.name().to_owned()).collect(),
+    }
+}
+
+fn render_column(name: &str, ast_field: &Field, docs: bool) -> D2SqlColumn {
+    let t = match ast_field.field_type {
+        FieldType::Supported(ref i) => &i.name,
+        FieldType::Unsupported(ref s, _) => s,
+    };
+    let mut column = D2SqlColumn::with_name_and_datatype(name.to_owned(), t.to_owned());
+    if docs {
+        column.doc = ast_field.documentation().map(str::to_owned);
+    }
+    for attr in &ast_field.attributes {
+        if attr.name.name == "id" {
+            column.constraints.push(SqlConstraint::PrimaryKey);
+        } else if attr.name.name == "unique" {
+            column.constraints.push(SqlConstraint::Unique);
+        }
+    }
+    column
+}
+
+/// Labels a field's edge to the enum or composite-type node its type names,
+/// so that those fields no longer render as dangling columns.
+fn composite_or_enum_label<'a>(
+    type_name: &str,
+    enum_names: &HashSet<&'a str>,
+    composite_names: &HashSet<&'a str>,
+) -> Option<&'static str> {
+    if enum_names.contains(type_name) {
+        Some("enum")
+    } else if composite_names.contains(type_name) {
+        Some("composite")
+    } else {
+        None
+    }
+}
+
+fn render_relation(relation: RelationWalker<'_>, diagram: &mut D2Diagram, cardinality_labels: bool) {
+    let label = cardinality_labels.then(|| {
+        if relation.is_many_to_many() {
+            "m:n"
+        } else if relation.is_one_to_one() {
+            "1:1"
+        } else {
+            "1:n"
+        }
+        .to_owned()
+    });
+
+    match relation.refine() {
+        RefinedRelationWalker::ImplicitManyToMany(m2m) => {
+            diagram.relations.push(D2Relation {
+                from: m2m.model_a().database_name().to_owned(),
+                to: m2m.model_b().database_name().to_owned(),
+                label: label.clone(),
+            });
+        }
+        RefinedRelationWalker::Inline(inline) => {
+            let referencing = inline.referencing_model();
+            let referenced = inline.referenced_model();
+            diagram.relations.push(D2Relation {
+                from: referenced.database_name().to_owned(),
+                to: referencing.database_name().to_owned(),
+                label: label.clone(),
+            });
+            if let Some(fields) = inline.referencing_fields() {
+                mark_foreign_keys(diagram, referencing.database_name(), fields);
+            }
+        }
+    }
+}
+
+fn mark_foreign_keys<'a>(
+    diagram: &mut D2Diagram,
+    table_name: &str,
+    fields: impl Iterator<Item = ScalarFieldWalker<'a>>,
+) {
+    let Some(table) = diagram.sql_tables.iter_mut().find(|t| t.name == table_name) else {
+        return;
+    };
+    for field in fields {
+        if let Some(column) = table.columns.iter_mut().find(|c| c.name == field.name()) {
+            column.constraints.push(SqlConstraint::ForeignKey);
+        }
+    }
+}
+
+impl Display for D2Diagram {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for table in &self.sql_tables {
+            write!(f, "{}\n\n", table)?;
+        }
+        for r#enum in &self.enums {
+            write!(f, "{}\n\n", r#enum)?;
+        }
+        for relation in &self.relations {
+            write!(f, "{}\n\n", relation)?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for D2SqlTable {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name)?;
+        match self.kind {
+            D2TableKind::Table => {}
+            D2TableKind::View => write!(f, ": \"{} (view)\"", self.name)?,
+            D2TableKind::Composite => write!(f, ": \"{} (composite type)\"", self.name)?,
+        }
+        write!(f, " {{\n")?;
+        write!(f, "\tshape: sql_table\n")?;
+        if let Some(doc) = &self.doc {
+            write!(f, "\ttooltip: {}\n", escape_d2_string(doc))?;
+        }
+        for column in &self.columns {
+            write!(f, "\t{}: {}", column.name, column.datatype)?;
+            if !column.constraints.is_empty() {
+                write!(
+                    f,
+                    " {{ constraint: [{}] }}",
+                    column
+                        .constraints
+                        .iter()
+                        .map(|c| c.to_string())
+                        .collect::<Vec<_>>()
+                        .join("; ")
+                )?;
+            }
+            if let Some(doc) = &column.doc {
+                write!(f, " # {}", doc.replace('\n', " "))?;
+            }
+            write!(f, "\n")?;
+        }
+        write!(f, "}}")?;
+        Ok(())
+    }
+}
+
+impl Display for D2Enum {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {{\n", self.name)?;
+        write!(f, "\tshape: class\n")?;
+        if let Some(doc) = &self.doc {
+            write!(f, "\ttooltip: {}\n", escape_d2_string(doc))?;
+        }
+        for value in &self.values {
+            write!(f, "\t{}\n", value)?;
+        }
+        write!(f, "}}")?;
+        Ok(())
+    }
+}
+
+/// Quotes `s` as a d2 string literal, escaping the characters that would
+/// otherwise end the literal or break the line.
+fn escape_d2_string(s: &str) -> String {
+    format!(
+        "\"{}\"",
+        s.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+    )
+}
+
+impl Display for SqlConstraint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SqlConstraint::ForeignKey => write!(f, "foreign_key"),
+            SqlConstraint::PrimaryKey => write!(f, "primary_key"),
+            SqlConstraint::Unique => write!(f, "unique"),
+        }
+    }
+}
+
+impl Display for D2Relation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} -> {}", self.from, self.to)?;
+        if let Some(ref label) = self.label {
+            write!(f, ": {}", label)?;
+        }
+        Ok(())
+    }
+}
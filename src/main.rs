@@ -1,212 +1,234 @@
 use std::{
-    fmt::Display,
     io::{BufReader, Read},
     path::PathBuf,
 };
 
 use clap::Parser;
-use psl::{
-    parse_schema,
-    parser_database::walkers::Walker,
-    schema_ast::ast::{FieldId, FieldType, ModelId},
-    ValidatedSchema,
-};
+use prisma2d2::{render_schema, Filtering, RenderOptions};
+use psl::SourceFile;
+use serde::Deserialize;
 
 /// Visualize a Prisma schema as a d2 diagram.
 #[derive(Debug, Parser)]
 #[command(version, about)]
 struct Args {
-    /// Parse the Prisma schema from a file.
+    /// Parse the Prisma schema from one or more files, or from a directory
+    /// containing `.prisma` files (all of them are parsed together, the same
+    /// way a multi-file Prisma project is).
     /// Defaults to stdin.
     #[arg()]
-    input_file: Option<PathBuf>,
+    input_file: Vec<PathBuf>,
     /// Write the d2 diagram to a file.
     /// Defaults to stdout.
     #[arg(short, long)]
     output_file: Option<PathBuf>,
-}
-
-struct D2Diagram {
-    sql_tables: Vec<D2SqlTable>,
-    relations: Vec<D2Relation>,
-}
-
-impl D2Diagram {
-    fn new() -> Self {
-        D2Diagram {
-            sql_tables: vec![],
-            relations: vec![],
+    /// Only render these models/enums. Mutually exclusive with `--except`.
+    #[arg(long, conflicts_with = "except")]
+    only: Vec<String>,
+    /// Render every model/enum except these. Mutually exclusive with `--only`.
+    #[arg(long, conflicts_with = "only")]
+    except: Vec<String>,
+    /// Don't render enum nodes.
+    #[arg(long)]
+    no_enums: bool,
+    /// Don't render relation edges.
+    #[arg(long)]
+    no_relations: bool,
+    /// Don't label relation edges with their cardinality.
+    #[arg(long)]
+    no_cardinality_labels: bool,
+    /// Don't surface `///` documentation comments on the diagram.
+    #[arg(long)]
+    no_docs: bool,
+}
+
+/// Mirrors every CLI flag so a project can check in a `prisma2d2.toml`
+/// instead of memorizing a long command line. CLI flags always win over
+/// whatever is set here.
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+struct Config {
+    input_file: Option<Vec<PathBuf>>,
+    output_file: Option<PathBuf>,
+    only: Option<Vec<String>>,
+    except: Option<Vec<String>>,
+    render_enums: Option<bool>,
+    render_relations: Option<bool>,
+    cardinality_labels: Option<bool>,
+    docs: Option<bool>,
+}
+
+/// Walks up from the working directory looking for `prisma2d2.toml`, the way
+/// e.g. a `.editorconfig` is discovered.
+fn discover_config_path() -> Option<PathBuf> {
+    let mut dir = std::env::current_dir().ok()?;
+    loop {
+        let candidate = dir.join("prisma2d2.toml");
+        if candidate.is_file() {
+            return Some(candidate);
+        }
+        if !dir.pop() {
+            return None;
         }
     }
 }
 
-struct D2SqlTable {
-    name: String,
-    columns: Vec<D2SqlColumn>,
-}
-
-impl D2SqlTable {
-    fn with_name(name: String) -> Self {
-        D2SqlTable {
-            name,
-            columns: vec![],
-        }
+fn load_config() -> Result<Config, Box<dyn std::error::Error>> {
+    match discover_config_path() {
+        Some(path) => Ok(toml::from_str(&std::fs::read_to_string(path)?)?),
+        None => Ok(Config::default()),
     }
 }
 
-struct D2SqlColumn {
-    name: String,
-    datatype: String,
-    constraints: Vec<SqlConstraint>,
+fn read_input(read: &mut dyn Read) -> Result<String, std::io::Error> {
+    let mut s = String::new();
+    read.read_to_string(&mut s)?;
+    Ok(s)
 }
 
-impl D2SqlColumn {
-    fn with_name_and_datatype(name: String, datatype: String) -> Self {
-        D2SqlColumn {
-            name,
-            datatype,
-            constraints: vec![],
+/// Expands directories in `paths` into the `.prisma` files they contain
+/// (sorted for deterministic output), leaving plain file paths untouched.
+fn collect_schema_paths(paths: Vec<PathBuf>) -> Result<Vec<PathBuf>, std::io::Error> {
+    let mut files = vec![];
+    for path in paths {
+        if path.is_dir() {
+            let mut entries: Vec<PathBuf> = std::fs::read_dir(&path)?
+                .filter_map(|entry| entry.ok())
+                .map(|entry| entry.path())
+                .filter(|path| path.extension().is_some_and(|ext| ext == "prisma"))
+                .collect();
+            entries.sort();
+            files.extend(entries);
+        } else {
+            files.push(path);
         }
     }
+    Ok(files)
 }
 
-enum SqlConstraint {
-    PrimaryKey,
-    ForeignKey,
-    Unique,
-}
-
-struct D2Relation {
-    from: String,
-    to: String,
-    label: Option<String>,
-}
-
-fn input_reader(path: Option<PathBuf>) -> Result<Box<dyn Read>, std::io::Error> {
-    if let Some(path) = path {
-        let f = std::fs::File::open(path)?;
-        Ok(Box::new(BufReader::new(f)))
+/// Reads `paths` (files and/or directories of `.prisma` files) into the
+/// `(filename, SourceFile)` pairs `render_schema` expects. With no paths
+/// given, reads a single schema from stdin instead.
+fn read_schema_sources(paths: Vec<PathBuf>) -> Result<Vec<(String, SourceFile)>, std::io::Error> {
+    if paths.is_empty() {
+        let input = read_input(&mut std::io::stdin())?;
+        return Ok(vec![("schema.prisma".to_owned(), input.into())]);
+    }
+    collect_schema_paths(paths)?
+        .into_iter()
+        .map(|path| {
+            let mut reader = BufReader::new(std::fs::File::open(&path)?);
+            let input = read_input(&mut reader)?;
+            Ok((path.to_string_lossy().into_owned(), input.into()))
+        })
+        .collect()
+}
+
+/// Resolves `--only`/`--except` against their `prisma2d2.toml` counterparts.
+///
+/// The CLI already refuses to combine `--only` and `--except` with each other
+/// (see `conflicts_with` above), but a config file can set one of them while
+/// the CLI sets the other. Whichever one the CLI actually passed must win
+/// outright, not just over its own config counterpart, or
+/// `Filtering::from_args`'s only-wins tiebreak can silently resurrect a config
+/// `only` that the user meant to override with `--except` (and vice versa).
+fn resolve_filtering_args(
+    cli_only: Vec<String>,
+    cli_except: Vec<String>,
+    config_only: Option<Vec<String>>,
+    config_except: Option<Vec<String>>,
+) -> (Vec<String>, Vec<String>) {
+    if !cli_only.is_empty() {
+        (cli_only, vec![])
+    } else if !cli_except.is_empty() {
+        (vec![], cli_except)
     } else {
-        Ok(Box::new(std::io::stdin()))
+        (config_only.unwrap_or_default(), config_except.unwrap_or_default())
     }
 }
 
-fn read_input(read: &mut dyn Read) -> Result<String, std::io::Error> {
-    let mut s = String::new();
-    read.read_to_string(&mut s)?;
-    Ok(s)
-}
-
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     let args = Args::parse();
-    let input = read_input(&mut input_reader(args.input_file)?)?;
-    let parsed = parse_schema(&input)?;
-    let diagram = render(&parsed);
-    println!("{}", diagram);
-    Ok(())
-}
+    let config = load_config()?;
 
-fn render(schema: &ValidatedSchema) -> D2Diagram {
-    let mut diagram = D2Diagram::new();
-    for model in schema.db.walk_models() {
-        let (table, mut relations) = render_model(model);
-        diagram.sql_tables.push(table);
-        diagram.relations.append(&mut relations);
-    }
-    diagram
-}
+    let input_file = if args.input_file.is_empty() {
+        config.input_file.unwrap_or_default()
+    } else {
+        args.input_file
+    };
+    let output_file = args.output_file.or(config.output_file);
+    let (only, except) =
+        resolve_filtering_args(args.only, args.except, config.only, config.except);
+    let options = RenderOptions {
+        render_enums: !args.no_enums && config.render_enums.unwrap_or(true),
+        render_relations: !args.no_relations && config.render_relations.unwrap_or(true),
+        cardinality_labels: !args.no_cardinality_labels && config.cardinality_labels.unwrap_or(true),
+        docs: !args.no_docs && config.docs.unwrap_or(true),
+    };
 
-fn render_model(model: Walker<'_, ModelId>) -> (D2SqlTable, Vec<D2Relation>) {
-    let mut table = D2SqlTable::with_name(model.name().to_owned());
-    let mut relations = vec![];
-    for field in model.fields() {
-        let (column, mut r) = render_field(model.name(), field);
-        table.columns.push(column);
-        relations.append(&mut r);
-    }
-    (table, relations)
-}
+    let sources = read_schema_sources(input_file)?;
+    let filtering = Filtering::from_args(only, except);
+    let diagram = render_schema(sources, &filtering, &options)?;
 
-fn render_field(
-    table_name: &str,
-    field: Walker<'_, (ModelId, FieldId)>,
-) -> (D2SqlColumn, Vec<D2Relation>) {
-    let f = field.ast_field();
-    let t = match f.field_type {
-        FieldType::Supported(ref i) => &i.name,
-        FieldType::Unsupported(ref s, _) => s,
-    };
-    let mut column = D2SqlColumn::with_name_and_datatype(field.name().to_owned(), t.to_owned());
-    let mut relations = vec![];
-    for attr in &f.attributes {
-        if attr.name.name == "id" {
-            column.constraints.push(SqlConstraint::PrimaryKey);
-        } else if attr.name.name == "unique" {
-            column.constraints.push(SqlConstraint::Unique);
-        } else if attr.name.name == "relation" {
-            relations.push(D2Relation {
-                from: table_name.to_owned(),
-                to: t.to_owned(),
-                label: None,
-            })
-        }
+    match output_file {
+        Some(path) => std::fs::write(path, diagram.to_string())?,
+        None => println!("{}", diagram),
     }
-    (column, relations)
+    Ok(())
 }
 
-impl Display for D2Diagram {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        for table in &self.sql_tables {
-            write!(f, "{}\n\n", table)?;
-        }
-        for relation in &self.relations {
-            write!(f, "{}\n\n", relation)?;
-        }
-        Ok(())
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cli_except_overrides_config_only() {
+        let (only, except) = resolve_filtering_args(
+            vec![],
+            vec!["Foo".to_owned()],
+            Some(vec!["Bar".to_owned()]),
+            None,
+        );
+        assert_eq!(only, Vec::<String>::new());
+        assert_eq!(except, vec!["Foo".to_owned()]);
     }
-}
 
-impl Display for D2SqlTable {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{} {{\n", self.name)?;
-        write!(f, "\tshape: sql_table\n")?;
-        for column in &self.columns {
-            write!(f, "\t{}: {}", column.name, column.datatype)?;
-            if !column.constraints.is_empty() {
-                write!(
-                    f,
-                    " {{ constraint: [{}] }}",
-                    column
-                        .constraints
-                        .iter()
-                        .map(|c| c.to_string())
-                        .collect::<Vec<_>>()
-                        .join("; ")
-                )?;
-            }
-            write!(f, "\n")?;
-        }
-        write!(f, "}}")?;
-        Ok(())
+    #[test]
+    fn cli_only_overrides_config_except() {
+        let (only, except) = resolve_filtering_args(
+            vec!["Foo".to_owned()],
+            vec![],
+            None,
+            Some(vec!["Bar".to_owned()]),
+        );
+        assert_eq!(only, vec!["Foo".to_owned()]);
+        assert_eq!(except, Vec::<String>::new());
     }
-}
 
-impl Display for SqlConstraint {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            SqlConstraint::ForeignKey => write!(f, "foreign_key"),
-            SqlConstraint::PrimaryKey => write!(f, "primary_key"),
-            SqlConstraint::Unique => write!(f, "unique"),
-        }
+    #[test]
+    fn falls_back_to_config_when_cli_sets_neither() {
+        let (only, except) = resolve_filtering_args(
+            vec![],
+            vec![],
+            Some(vec!["Bar".to_owned()]),
+            None,
+        );
+        assert_eq!(only, vec!["Bar".to_owned()]);
+        assert_eq!(except, Vec::<String>::new());
     }
-}
 
-impl Display for D2Relation {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{} -> {}", self.from, self.to)?;
-        if let Some(ref label) = self.label {
-            write!(f, ": {}", label)?;
-        }
-        Ok(())
+    #[test]
+    fn collect_schema_paths_expands_directories_sorted() {
+        let dir = std::env::temp_dir().join(format!("prisma2d2-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("b.prisma"), "").unwrap();
+        std::fs::write(dir.join("a.prisma"), "").unwrap();
+        std::fs::write(dir.join("ignored.txt"), "").unwrap();
+
+        let files = collect_schema_paths(vec![dir.clone()]).unwrap();
+
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(files, vec![dir.join("a.prisma"), dir.join("b.prisma")]);
     }
 }
@@ -0,0 +1,131 @@
+use std::{fs, path::Path};
+
+use prisma2d2::{render_schema, Filtering, RenderOptions};
+
+/// Renders `tests/fixtures/<name>.prisma` and compares it against
+/// `tests/fixtures/<name>.d2`. Set `PRISMA2D2_BLESS=1` to (re)write the
+/// expectation file from the current output instead of asserting.
+fn check_golden(name: &str) {
+    let fixture_path = Path::new("tests/fixtures").join(format!("{name}.prisma"));
+    let expectation_path = Path::new("tests/fixtures").join(format!("{name}.d2"));
+
+    let schema = fs::read_to_string(&fixture_path)
+        .unwrap_or_else(|e| panic!("failed to read {}: {e}", fixture_path.display()));
+    let diagram = render_schema(
+        vec![(name.to_owned(), schema.into())],
+        &Filtering::None,
+        &RenderOptions::default(),
+    )
+    .unwrap_or_else(|e| panic!("failed to render {name}: {e}"));
+    let actual = diagram.to_string();
+
+    if std::env::var_os("PRISMA2D2_BLESS").is_some() {
+        fs::write(&expectation_path, &actual)
+            .unwrap_or_else(|e| panic!("failed to write {}: {e}", expectation_path.display()));
+        return;
+    }
+
+    let expected = fs::read_to_string(&expectation_path).unwrap_or_else(|e| {
+        panic!(
+            "failed to read {}: {e} (run with PRISMA2D2_BLESS=1 to create it)",
+            expectation_path.display()
+        )
+    });
+    assert_eq!(actual, expected, "{name}: diagram does not match the golden file");
+}
+
+/// Like `check_golden`, but renders every `.prisma` file in
+/// `tests/fixtures/<dir_name>/` together as one multi-file schema and
+/// compares the result against `tests/fixtures/<dir_name>.d2`. Used to cover
+/// cross-file relations, which a single-schema fixture can't exercise.
+fn check_multi_file_golden(dir_name: &str) {
+    let fixture_dir = Path::new("tests/fixtures").join(dir_name);
+    let expectation_path = Path::new("tests/fixtures").join(format!("{dir_name}.d2"));
+
+    let mut file_names: Vec<_> = fs::read_dir(&fixture_dir)
+        .unwrap_or_else(|e| panic!("failed to read {}: {e}", fixture_dir.display()))
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "prisma"))
+        .collect();
+    file_names.sort();
+
+    let sources = file_names
+        .into_iter()
+        .map(|path| {
+            let schema = fs::read_to_string(&path)
+                .unwrap_or_else(|e| panic!("failed to read {}: {e}", path.display()));
+            (path.to_string_lossy().into_owned(), schema.into())
+        })
+        .collect();
+    let diagram = render_schema(sources, &Filtering::None, &RenderOptions::default())
+        .unwrap_or_else(|e| panic!("failed to render {dir_name}: {e}"));
+    let actual = diagram.to_string();
+
+    if std::env::var_os("PRISMA2D2_BLESS").is_some() {
+        fs::write(&expectation_path, &actual)
+            .unwrap_or_else(|e| panic!("failed to write {}: {e}", expectation_path.display()));
+        return;
+    }
+
+    let expected = fs::read_to_string(&expectation_path).unwrap_or_else(|e| {
+        panic!(
+            "failed to read {}: {e} (run with PRISMA2D2_BLESS=1 to create it)",
+            expectation_path.display()
+        )
+    });
+    assert_eq!(
+        actual, expected,
+        "{dir_name}: diagram does not match the golden file"
+    );
+}
+
+#[test]
+fn primary_key() {
+    check_golden("primary_key");
+}
+
+#[test]
+fn unique_constraint() {
+    check_golden("unique_constraint");
+}
+
+#[test]
+fn foreign_key() {
+    check_golden("foreign_key");
+}
+
+#[test]
+fn enum_field() {
+    check_golden("enum_field");
+}
+
+#[test]
+fn one_to_one_relation() {
+    check_golden("one_to_one_relation");
+}
+
+#[test]
+fn many_to_many_relation() {
+    check_golden("many_to_many_relation");
+}
+
+#[test]
+fn view() {
+    check_golden("view");
+}
+
+#[test]
+fn composite_type() {
+    check_golden("composite_type");
+}
+
+#[test]
+fn docs() {
+    check_golden("docs");
+}
+
+#[test]
+fn multi_file_relation() {
+    check_multi_file_golden("multi_file");
+}
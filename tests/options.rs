@@ -0,0 +1,47 @@
+use std::fs;
+
+use prisma2d2::{render_schema, Filtering, RenderOptions};
+
+fn read_fixture(name: &str) -> String {
+    let path = format!("tests/fixtures/{name}.prisma");
+    fs::read_to_string(&path).unwrap_or_else(|e| panic!("failed to read {path}: {e}"))
+}
+
+#[test]
+fn except_filtering_excludes_composite_types() {
+    let schema = read_fixture("composite_type");
+    let diagram = render_schema(
+        vec![("composite_type".to_owned(), schema.into())],
+        &Filtering::ExceptTables(vec!["Address".to_owned()]),
+        &RenderOptions::default(),
+    )
+    .unwrap_or_else(|e| panic!("failed to render: {e}"));
+    let rendered = diagram.to_string();
+
+    // `User.address` keeps its `Address` datatype as a column; only the
+    // composite node and the edge to it are dropped.
+    assert!(
+        !rendered.contains("(composite type)") && !rendered.contains("-> Address"),
+        "excluded composite type still rendered:\n{rendered}"
+    );
+}
+
+#[test]
+fn render_relations_false_suppresses_enum_edges() {
+    let schema = read_fixture("docs");
+    let diagram = render_schema(
+        vec![("docs".to_owned(), schema.into())],
+        &Filtering::None,
+        &RenderOptions {
+            render_relations: false,
+            ..RenderOptions::default()
+        },
+    )
+    .unwrap_or_else(|e| panic!("failed to render: {e}"));
+    let rendered = diagram.to_string();
+
+    assert!(
+        !rendered.contains("-> Role"),
+        "enum edge rendered despite render_relations: false:\n{rendered}"
+    );
+}